@@ -1,8 +1,11 @@
+// No caller in this crate drives an MFCC training loop yet; kept compiled
+// in alongside `models`, same as `tui::screenshot`.
+#![allow(dead_code)]
+
 use std::iter::zip;
 
-use anyhow::Result;
 use ndarray::{s, Array, Array1, Array2, Axis};
-use ndrustfft::{nddct1, nddct2, ndfft_r2c, Complex, DctHandler, R2cFftHandler};
+use ndrustfft::{nddct2, ndfft_r2c, Complex, DctHandler, R2cFftHandler};
 
 pub fn safe_log2(x: Array2<f32>) -> Array2<f32> {
     let x = x.mapv(|v| v.min(f32::EPSILON).ln());
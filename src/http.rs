@@ -0,0 +1,78 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::Result;
+use reqwest::{Method, Url};
+use serde::Serialize;
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: Option<String>,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+async fn send_once(req: &HttpRequest, method: Method, url: Url) -> Result<HttpResponse> {
+    let client = reqwest::Client::new();
+    let mut builder = client.request(method, url).timeout(req.timeout);
+    for (key, value) in req.headers.iter() {
+        builder = builder.header(key, value);
+    }
+    if let Some(body) = &req.body {
+        builder = builder.body(body.clone());
+    }
+    let response = builder.send().await?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
+        .collect();
+    let body = response.text().await?;
+    Ok(HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// A transient failure is a connection/timeout problem or a 5xx response —
+/// never a malformed request (invalid header, bad builder state), which
+/// would just fail identically on every retry.
+fn is_transient(result: &Result<HttpResponse>) -> bool {
+    match result {
+        Ok(response) => response.status >= 500,
+        Err(e) => match e.downcast_ref::<reqwest::Error>() {
+            Some(e) => !e.is_builder(),
+            None => true,
+        },
+    }
+}
+
+/// Sends `req`, retrying transient failures (connection errors and 5xx
+/// responses) with exponential backoff, up to `retries` extra attempts.
+/// The method and URL are parsed once up front: a typo'd method or
+/// malformed URL is a deterministic error and fails immediately instead
+/// of burning through the retry budget.
+pub async fn request(req: HttpRequest, retries: u32) -> Result<HttpResponse> {
+    let method = Method::from_bytes(req.method.as_bytes())?;
+    let url: Url = req.url.parse()?;
+    let mut attempt = 0;
+    loop {
+        let result = send_once(&req, method.clone(), url.clone()).await;
+        if !is_transient(&result) || attempt >= retries {
+            return result;
+        }
+        attempt += 1;
+        let backoff = Duration::from_millis(200 * 2u64.pow(attempt.min(5)));
+        tokio::time::sleep(backoff).await;
+    }
+}
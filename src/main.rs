@@ -1,7 +1,14 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    io::Read as _,
+    path::Path,
     str::FromStr,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::RecvTimeoutError,
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 
@@ -10,6 +17,8 @@ use color_eyre::eyre::{bail, Result};
 use crossterm::event::{self, poll, KeyCode, KeyEventKind};
 use dotenv::dotenv;
 use futures::{SinkExt, StreamExt};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Flex, Layout, Margin, Rect},
@@ -28,6 +37,12 @@ use ratatui_image::{
 use rustpython_vm::{self as vm, convert::ToPyObject, scope::Scope, AsObject, PyResult};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Color as SyntectColor, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::broadcast::{channel, Receiver, Sender};
 use tokio_tungstenite::tungstenite::Message;
@@ -35,7 +50,10 @@ use tui_big_text::{BigText, PixelSize};
 
 mod actions;
 mod errors;
+mod fft;
+mod http;
 mod log;
+mod models;
 mod modules;
 mod tui;
 
@@ -78,21 +96,43 @@ async fn main() -> color_eyre::Result<()> {
     let try_socket = TcpListener::bind(bind.to_owned()).await;
     let listener = try_socket.expect(&format!("Failed to bind {}", bind.as_str()));
     let (sender, _) = channel::<Vec<u8>>(128);
+    let (frame_sender, _) = channel::<Buffer>(32);
+    let (input_sender, input_receiver) = channel::<ClientInputEvent>(128);
+    let (reload_sender, reload_receiver) = channel::<String>(128);
+    let _script_watcher = watch_scripts(reload_sender)?;
+    let widget_areas: Arc<Mutex<HashMap<String, Rect>>> = Arc::new(Mutex::new(HashMap::new()));
 
     let mut terminal = tui::init()?;
     let cloned_sender = sender.clone();
+    let cloned_frame_sender = frame_sender.clone();
     let cloned_init = init_buffer.clone();
+    let cloned_input_sender = input_sender.clone();
+    let cloned_widget_areas = widget_areas.clone();
     let ws_handle = tokio::spawn(async move {
         while let Ok((stream, _)) = listener.accept().await {
             tokio::spawn(serve(
                 stream,
                 cloned_sender.subscribe(),
+                cloned_frame_sender.subscribe(),
                 cloned_init.clone(),
+                cloned_input_sender.clone(),
+                cloned_widget_areas.clone(),
             ));
         }
     });
 
-    let result = App::new(actions, size, init_buffer.clone(), sender).run(&mut terminal).await;
+    let result = App::new(
+        actions,
+        size,
+        init_buffer.clone(),
+        sender,
+        frame_sender,
+        input_receiver,
+        reload_receiver,
+        widget_areas,
+    )
+    .run(&mut terminal)
+    .await;
 
     tui::restore()?;
     ws_handle.abort();
@@ -105,18 +145,163 @@ async fn main() -> color_eyre::Result<()> {
     }
 }
 
+/// A key event decoded from a client's command-byte-1 frame.
+#[derive(Deserialize, Clone)]
+struct KeyInputEvent {
+    code: String,
+    #[serde(default)]
+    modifiers: Vec<String>,
+}
+
+/// A mouse/click event decoded from a client's command-byte-3 frame, carrying
+/// the name of the widget the browser resolved the click to.
+#[derive(Deserialize, Clone)]
+struct ClickInputEvent {
+    name: String,
+    x: u16,
+    y: u16,
+}
+
+#[derive(Clone)]
+enum ClientInputEvent {
+    Key(KeyInputEvent),
+    Click(ClickInputEvent),
+}
+
+/// Watches the `scripts` directory and pushes the stem of each changed
+/// `.py` file into `tx`, debouncing bursts of events (editors tend to emit
+/// several writes per save) within a ~200ms window so one save yields one
+/// reload signal.
+fn watch_scripts(tx: Sender<String>) -> notify::Result<RecommendedWatcher> {
+    let (events_tx, events_rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            let _ = events_tx.send(event);
+        }
+    })?;
+    watcher.watch(Path::new("scripts"), RecursiveMode::NonRecursive)?;
+    std::thread::spawn(move || {
+        let mut pending: HashSet<String> = HashSet::new();
+        loop {
+            match events_rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(event) => {
+                    if !matches!(
+                        event.kind,
+                        EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)
+                    ) {
+                        continue;
+                    }
+                    for path in event.paths {
+                        if path.extension().and_then(OsStr::to_str) != Some("py") {
+                            continue;
+                        }
+                        if let Some(stem) = path.file_stem().and_then(OsStr::to_str) {
+                            pending.insert(stem.to_string());
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    for name in pending.drain() {
+                        let _ = tx.send(name);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+    Ok(watcher)
+}
+
+/// Reads the token table from the environment: `DASHBOARD_TOKEN` is a single
+/// full-access token, and `DASHBOARD_TOKEN_SCOPES` is an optional JSON object
+/// mapping additional tokens to the subset of widget names they may see.
+/// An empty table means the dashboard is unauthenticated: every client that
+/// completes the websocket handshake is served.
+fn load_tokens() -> HashMap<String, Option<Vec<String>>> {
+    let mut tokens = HashMap::new();
+    if let Ok(token) = std::env::var("DASHBOARD_TOKEN") {
+        if !token.is_empty() {
+            tokens.insert(token, None);
+        }
+    }
+    if let Ok(raw) = std::env::var("DASHBOARD_TOKEN_SCOPES") {
+        if let Ok(scoped) = serde_json::from_str::<HashMap<String, Vec<String>>>(&raw) {
+            for (token, widgets) in scoped {
+                tokens.insert(token, Some(widgets));
+            }
+        }
+    }
+    tokens
+}
+
+fn resolve_rects(names: &[String], widget_areas: &HashMap<String, Rect>) -> Vec<Rect> {
+    names.iter().filter_map(|n| widget_areas.get(n).cloned()).collect()
+}
+
+/// Waits (with no timeout of its own - the caller wraps this in one) for the
+/// client's command byte 4 frame carrying the shared-secret token, ignoring
+/// anything else sent before it.
+async fn wait_for_token(
+    ws_receiver: &mut futures::stream::SplitStream<
+        tokio_tungstenite::WebSocketStream<TcpStream>,
+    >,
+) -> Option<String> {
+    while let Some(msg) = ws_receiver.next().await {
+        match msg {
+            Ok(Message::Binary(msg)) if msg.get(0).cloned() == Some(4) => {
+                return std::str::from_utf8(&msg[1..]).ok().map(|s| s.to_string());
+            }
+            Ok(Message::Close(_)) | Err(_) => return None,
+            _ => continue,
+        }
+    }
+    None
+}
+
 async fn serve(
     stream: TcpStream,
     mut receiver: Receiver<Vec<u8>>,
+    mut frame_receiver: Receiver<Buffer>,
     init_buffer: Arc<Mutex<Buffer>>,
+    input_sender: Sender<ClientInputEvent>,
+    widget_areas: Arc<Mutex<HashMap<String, Rect>>>,
 ) {
     let ws_stream = tokio_tungstenite::accept_async(stream)
         .await
         .expect("Error during the websocket handshake occurred");
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-    let buffer = init_buffer.lock().unwrap().clone();
+
+    let tokens = load_tokens();
+    let scope: Option<Vec<String>> = if tokens.is_empty() {
+        None
+    } else {
+        let token = tokio::time::timeout(Duration::from_secs(5), wait_for_token(&mut ws_receiver))
+            .await
+            .ok()
+            .flatten();
+        match token.and_then(|token| tokens.get(&token).cloned()) {
+            Some(scope) => scope,
+            None => {
+                let _ = ws_sender.send(Message::Close(None)).await;
+                return;
+            }
+        }
+    };
+
+    let mask = |buffer: Buffer| -> Buffer {
+        match &scope {
+            Some(names) => {
+                let areas = widget_areas.lock().unwrap();
+                tui::mask_buffer(&buffer, &resolve_rects(names, &areas))
+            }
+            None => buffer,
+        }
+    };
+
+    let buffer = mask(init_buffer.lock().unwrap().clone());
     let default_buffer = Buffer::empty(buffer.area);
     let output = tui::to_ansi(buffer.clone(), default_buffer);
+    let mut last_buffer = buffer.clone();
 
     let mut byte_array = json!({
         "cols": buffer.area.height,
@@ -131,11 +316,48 @@ async fn serve(
     let _ = ws_sender.send(Message::Binary(byte_array)).await;
     loop {
         tokio::select! {
+            msg = frame_receiver.recv() => {
+                match msg {
+                    Ok(buffer) => {
+                        let buffer = mask(buffer);
+                        let output = tui::to_ansi(buffer.clone(), last_buffer.clone());
+                        last_buffer = buffer;
+                        if output.is_empty() {
+                            continue;
+                        }
+                        let mut byte_array = output.into_bytes();
+                        byte_array.insert(0, 0);
+                        let result = ws_sender.send(Message::Binary(byte_array)).await;
+                        match result {
+                            Ok(_) => {}
+                            Err(_) => break,
+                        }
+                    }
+                    _ => break,
+                }
+            }
             msg = ws_receiver.next() => {
                 match msg {
                     Some(Ok(Message::Binary(msg))) => {
                         let cmd = msg.get(0).cloned().unwrap_or(0);
                         if cmd == 1 {
+                            match serde_json::from_slice::<KeyInputEvent>(&msg[1..]) {
+                                Ok(key) => {
+                                    let _ = input_sender.send(ClientInputEvent::Key(key));
+                                }
+                                Err(e) => {
+                                    let _ = log::println(&format!("InputKeyError: {:?}", e));
+                                }
+                            }
+                        } else if cmd == 3 {
+                            match serde_json::from_slice::<ClickInputEvent>(&msg[1..]) {
+                                Ok(click) => {
+                                    let _ = input_sender.send(ClientInputEvent::Click(click));
+                                }
+                                Err(e) => {
+                                    let _ = log::println(&format!("InputClickError: {:?}", e));
+                                }
+                            }
                         }
                     }
                     _ => break,
@@ -187,7 +409,17 @@ pub struct App<'a> {
     screenshot: String,
 
     ws_sender: Sender<Vec<u8>>,
+    frame_sender: Sender<Buffer>,
     init_buffer: Arc<Mutex<Buffer>>,
+    input_recv: Receiver<ClientInputEvent>,
+    reload_recv: Receiver<String>,
+    widget_areas: Arc<Mutex<HashMap<String, Rect>>>,
+    syntax_set: SyntaxSet,
+    theme_set: ThemeSet,
+    intervals: HashMap<String, Duration>,
+    next_run: HashMap<String, Instant>,
+    recorder: Option<tui::Recorder>,
+    last_recorded_buffer: Buffer,
 
     resized: bool,
 }
@@ -206,9 +438,27 @@ enum WidgetState<'a> {
     BigText(BigTextWidget),
     ColorText(ColorTextWidget<'a>),
     Chart(ChartWidget),
+    Terminal(TerminalWidget),
+    Code(CodeWidget),
     Blank,
 }
 
+#[derive(Clone)]
+struct CodeWidget {
+    name: String,
+    lines: Vec<Line<'static>>,
+}
+
+#[derive(Clone)]
+struct TerminalWidget {
+    name: String,
+    parser: Arc<Mutex<vt100::Parser>>,
+    master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
+    child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    alive: Arc<AtomicBool>,
+    area: Rect,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 struct TodoWidget {
     text: String,
@@ -290,6 +540,18 @@ fn check_int(value: Option<serde_json::Value>) -> i64 {
     num
 }
 
+fn syntect_to_ratatui_color(color: SyntectColor) -> Color {
+    Color::Rgb(color.r, color.g, color.b)
+}
+
+fn vt100_to_ratatui_color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(i) => Color::Indexed(i),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
 fn check_bool(value: Option<serde_json::Value>, default: bool) -> bool {
     let value = match value {
         Some(value) => value,
@@ -308,6 +570,10 @@ impl App<'_> {
         size: Option<(u16, u16)>,
         init_buffer: Arc<Mutex<Buffer>>,
         sender: Sender<Vec<u8>>,
+        frame_sender: Sender<Buffer>,
+        input_recv: Receiver<ClientInputEvent>,
+        reload_recv: Receiver<String>,
+        widget_areas: Arc<Mutex<HashMap<String, Rect>>>,
     ) -> Self {
         let mut settings = vm::Settings::default();
         settings.allow_external_library = true;
@@ -352,6 +618,16 @@ impl App<'_> {
             screenshot: String::new(),
             init_buffer,
             ws_sender: sender,
+            frame_sender,
+            input_recv,
+            reload_recv,
+            widget_areas,
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            theme_set: ThemeSet::load_defaults(),
+            intervals: HashMap::new(),
+            next_run: HashMap::new(),
+            recorder: None,
+            last_recorded_buffer: Buffer::default(),
             resized: false,
         }
     }
@@ -363,6 +639,8 @@ impl App<'_> {
         }
         let _ = terminal.clear();
         self.modules.clear();
+        self.intervals.clear();
+        self.next_run.clear();
         self.widgets.retain(|key, _v| key.starts_with("task_"));
         self.failed.clear();
         self.actions = actions::initialize_scripts()?;
@@ -439,6 +717,7 @@ impl App<'_> {
             });
             match result {
                 Ok(res) => {
+                    self.schedule_module(&action.name, &res);
                     self.modules.insert(action.name, res);
                 }
                 Err(e) => {
@@ -464,7 +743,6 @@ impl App<'_> {
                 .expect("add path");
         });
         self.init(terminal)?;
-        let mut time = Instant::now();
         while !self.exit {
             let mut temp_buf = Buffer::default();
             terminal.draw(|frame| {
@@ -481,22 +759,20 @@ impl App<'_> {
                 //     });
                 // }
             })?;
-            if time.elapsed().as_millis() > 1000 {
-                time = Instant::now();
-                self.exec()?;
-                let output =
-                    tui::to_ansi(temp_buf.clone(), self.init_buffer.lock().unwrap().clone());
-                *self.init_buffer.lock().unwrap() = temp_buf;
-
-                if output.len() == 0 {
-                    continue;
+            if let Some(recorder) = self.recorder.as_mut() {
+                let output = tui::to_ansi(temp_buf.clone(), self.last_recorded_buffer.clone());
+                self.last_recorded_buffer = temp_buf.clone();
+                if !output.is_empty() {
+                    let _ = recorder.record(&output);
                 }
-                let mut byte_array = output.into_bytes();
-                byte_array.insert(0, 0);
-                let _ = self.ws_sender.send(byte_array);
-                // *self.last_buffer.lock().unwrap() = terminal.current_buffer_mut().clone();
             }
+            *self.init_buffer.lock().unwrap() = temp_buf.clone();
+            let _ = self.frame_sender.send(temp_buf);
+            // *self.last_buffer.lock().unwrap() = terminal.current_buffer_mut().clone();
+            self.exec_due()?;
             self.handle_events(terminal)?;
+            self.dispatch_input();
+            self.reload_changed_modules()?;
             self.consumer(terminal)?;
             tokio::time::sleep(Duration::from_millis(100)).await;
         }
@@ -605,8 +881,85 @@ impl App<'_> {
                 });
                 self.widgets.insert(data.name.to_owned(), state);
             }
+            "code" => {
+                let text = check_str(value.get("text").cloned());
+                let language = check_str(value.get("language").cloned());
+                let theme_name = check_str(value.get("theme").cloned());
+                let line_numbers = check_bool(value.get("line_numbers").cloned(), false);
+                let syntax = self
+                    .syntax_set
+                    .find_syntax_by_token(&language)
+                    .or_else(|| self.syntax_set.find_syntax_by_extension(&language))
+                    .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+                let theme = self
+                    .theme_set
+                    .themes
+                    .get(theme_name.as_str())
+                    .unwrap_or(&self.theme_set.themes["base16-ocean.dark"]);
+                let mut highlighter = HighlightLines::new(syntax, theme);
+                let mut lines = Vec::new();
+                for (i, line) in LinesWithEndings::from(&text).enumerate() {
+                    let ranges = match highlighter.highlight_line(line, &self.syntax_set) {
+                        Ok(ranges) => ranges,
+                        Err(e) => {
+                            let _ = log::println(&e.to_string());
+                            continue;
+                        }
+                    };
+                    let mut spans = Vec::new();
+                    if line_numbers {
+                        spans.push(Span::styled(
+                            format!("{:>4} ", i + 1),
+                            Style::new().dark_gray(),
+                        ));
+                    }
+                    for (style, text) in ranges {
+                        spans.push(Span::styled(
+                            text.trim_end_matches(['\n', '\r']).to_string(),
+                            Style::new().fg(syntect_to_ratatui_color(style.foreground)),
+                        ));
+                    }
+                    lines.push(Line::from(spans));
+                }
+                let state = WidgetState::Code(CodeWidget {
+                    name: data.name.to_owned(),
+                    lines,
+                });
+                self.widgets.insert(data.name.to_owned(), state);
+            }
             "clear" => {
-                self.widgets.remove(&data.name);
+                if let Some(WidgetState::Terminal(term)) = self.widgets.remove(&data.name) {
+                    term.alive.store(false, Ordering::SeqCst);
+                    let child = term.child.clone();
+                    let _ = child.lock().unwrap().kill();
+                    std::thread::spawn(move || {
+                        let _ = child.lock().unwrap().wait();
+                    });
+                }
+            }
+            "terminal" => {
+                let command = check_str(value.get("command").cloned());
+                if command.is_empty() {
+                    let _ = log::println("no command");
+                    return Ok(());
+                }
+                let args = value
+                    .get("args")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                match self.spawn_terminal(data.name.clone(), command, args) {
+                    Ok(widget) => {
+                        self.widgets.insert(data.name.to_owned(), widget);
+                    }
+                    Err(e) => {
+                        let _ = log::println(&format!("TerminalSpawnError: {:?}", e));
+                    }
+                }
             }
             "big" => {
                 let text = check_str(value.get("text").cloned());
@@ -699,6 +1052,26 @@ impl App<'_> {
                 byte_array.insert(0, 1);
                 let _ = self.ws_sender.send(byte_array);
             }
+            "start_recording" => {
+                let path = check_str(value.get("path").cloned());
+                if path.is_empty() {
+                    let _ = log::println("no path");
+                    return Ok(());
+                }
+                let area = terminal.size()?;
+                match tui::Recorder::start(&path, area.width, area.height) {
+                    Ok(recorder) => {
+                        self.last_recorded_buffer = Buffer::empty(area);
+                        self.recorder = Some(recorder);
+                    }
+                    Err(e) => {
+                        let _ = log::println(&format!("RecordingStartError: {:?}", e));
+                    }
+                }
+            }
+            "stop_recording" => {
+                self.recorder = None;
+            }
             "exit" => {
                 self.exit();
             }
@@ -706,51 +1079,258 @@ impl App<'_> {
         }
         Ok(())
     }
-    pub fn sort_todo(&mut self) {
-        self.state.todo.sort_by(|a, b| a.deadline.cmp(&b.deadline));
-        self.state.todo.sort_by(|a, b| a.done.cmp(&b.done));
+    /// Dispatches one `on_key`/`on_click` call on its own `vm.start_thread`
+    /// thread, same as `exec_one`, so a slow handler stalls neither the
+    /// render loop nor any other module's scheduling.
+    fn dispatch_handler(&self, name: &str, module: &Scope, run: impl FnOnce(&vm::VirtualMachine, &Scope) + Send + 'static) {
+        let module = module.clone();
+        let block: PyResult<()> = self.interpreter.enter(|vm| {
+            vm.start_thread(move |vm| run(vm, &module));
+            Ok(())
+        });
+        if let Err(e) = block {
+            self.interpreter.enter(|vm| {
+                let _ = log::println(&format!("[{}] {}", name, e.as_object().repr(vm).unwrap().as_str()));
+            });
+        }
     }
-    pub fn exec(&mut self) -> Result<()> {
-        for (name, module) in self.modules.iter() {
-            let block: PyResult<()> = self.interpreter.enter(|vm| {
-                let module = module.clone();
-                vm.start_thread(move |vm| {
-                    let res = module
-                        .locals
-                        .get_item("update", vm)
-                        .unwrap_or(vm.new_function("update", || {}).to_pyobject(vm));
-                    let result = res.call((), vm);
-                    match result {
-                        Err(e) => {
+
+    /// Drains queued browser input events and routes them into Python
+    /// modules by calling their optional `on_key`/`on_click` function: key
+    /// events go to every loaded module, click events to the module named by
+    /// the click.
+    fn dispatch_input(&mut self) {
+        while let Ok(event) = self.input_recv.try_recv() {
+            match event {
+                ClientInputEvent::Key(key) => {
+                    let modifiers = key.modifiers.clone();
+                    for (name, module) in self.modules.iter() {
+                        let code = key.code.clone();
+                        let modifiers = modifiers.clone();
+                        let name = name.clone();
+                        self.dispatch_handler(&name, module, move |vm, module| {
+                            let handler = match module.locals.get_item("on_key", vm) {
+                                Ok(f) => f,
+                                Err(_) => return,
+                            };
+                            if let Err(e) = handler.call((code, modifiers), vm) {
+                                let _ = log::println(&format!(
+                                    "[{}] on_key: {}",
+                                    name,
+                                    e.to_pyobject(vm).repr(vm).unwrap().as_str()
+                                ));
+                            }
+                        });
+                    }
+                }
+                ClientInputEvent::Click(click) => {
+                    let module = match self.modules.get(&click.name) {
+                        Some(module) => module.clone(),
+                        None => continue,
+                    };
+                    let name = click.name.clone();
+                    self.dispatch_handler(&name, &module, move |vm, module| {
+                        let handler = match module.locals.get_item("on_click", vm) {
+                            Ok(f) => f,
+                            Err(_) => return,
+                        };
+                        if let Err(e) = handler.call((click.x, click.y), vm) {
                             let _ = log::println(&format!(
-                                "E: {}",
-                                e.clone().to_pyobject(vm).repr(vm).unwrap().as_str()
+                                "[{}] on_click: {}",
+                                name,
+                                e.to_pyobject(vm).repr(vm).unwrap().as_str()
                             ));
-                            let traceback = e.traceback().unwrap();
-                            for tb in traceback.iter() {
-                                let _ = log::println(&format!("Traceback: {:?}", tb.frame.code,));
-                            }
                         }
-                        _ => {}
-                    }
-                });
-                Ok(())
-            });
-            match block {
-                Ok(_) => {}
-                Err(e) => {
-                    self.interpreter.enter(|vm| {
-                        let _ = log::println(&format!(
-                            "[{}] {}",
-                            name.to_owned(),
-                            e.as_object().repr(vm).unwrap().as_str()
-                        ));
                     });
                 }
             }
         }
+    }
+    /// Reloads each module the script watcher reported changed, recompiling
+    /// just that file and replacing its `Scope` in `self.modules` in place.
+    /// Unaffected widgets and `task_` threads are left untouched.
+    fn reload_changed_modules(&mut self) -> Result<()> {
+        let mut names = Vec::new();
+        while let Ok(name) = self.reload_recv.try_recv() {
+            names.push(name);
+        }
+        for name in names {
+            self.reload_module(&name)?;
+        }
+        Ok(())
+    }
+
+    fn reload_module(&mut self, name: &str) -> Result<()> {
+        if name.starts_with("task_") {
+            return Ok(());
+        }
+        let path = format!("scripts/{}.py", name);
+        let source = match std::fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(_) => {
+                self.modules.remove(name);
+                self.intervals.remove(name);
+                self.next_run.remove(name);
+                return Ok(());
+            }
+        };
+        let result: vm::PyResult<vm::scope::Scope> = self.interpreter.enter(|vm| {
+            let scp = vm.new_scope_with_builtins();
+            let code_obj = vm
+                .compile(&source, vm::compiler::Mode::Exec, name.to_owned() + ".py")
+                .map_err(|err| vm.new_syntax_error(&err, Some(&source)))?;
+            vm.run_code_obj(code_obj, scp.clone())?;
+            let init_fn = scp.locals.get_item("init", vm)?;
+            init_fn.call((), vm)?;
+            Ok(scp)
+        });
+        match result {
+            Ok(scope) => {
+                self.schedule_module(name, &scope);
+                self.modules.insert(name.to_owned(), scope);
+                self.failed.retain(|n| n != name);
+                log::println(&format!("Reloaded: {}", name))?;
+            }
+            Err(e) => {
+                let err = self
+                    .interpreter
+                    .enter(|vm| match e.to_pyobject(vm).repr(vm) {
+                        Ok(err) => err.as_str().to_string(),
+                        Err(_) => "ERROR0111".to_owned(),
+                    });
+                log::println(&format!("ReloadError[{}]: {}", name, err))?;
+            }
+        }
+        Ok(())
+    }
+    pub fn sort_todo(&mut self) {
+        self.state.todo.sort_by(|a, b| a.deadline.cmp(&b.deadline));
+        self.state.todo.sort_by(|a, b| a.done.cmp(&b.done));
+    }
+    /// Reads a module's declared refresh interval from its `INTERVAL`
+    /// global (seconds, as an int or float), defaulting to one second when
+    /// absent or unparsable.
+    fn module_interval(&self, module: &Scope) -> Duration {
+        let seconds = self.interpreter.enter(|vm| {
+            module
+                .locals
+                .get_item("INTERVAL", vm)
+                .ok()
+                .and_then(|v| v.try_into_value::<f64>(vm).ok())
+        });
+        Duration::from_secs_f64(seconds.unwrap_or(1.0).max(0.01))
+    }
+
+    /// (Re-)schedules a module to run on its next declared interval,
+    /// starting from now.
+    fn schedule_module(&mut self, name: &str, module: &Scope) {
+        let interval = self.module_interval(module);
+        self.intervals.insert(name.to_owned(), interval);
+        self.next_run.insert(name.to_owned(), Instant::now() + interval);
+    }
+
+    /// Runs every module whose `next_run` deadline has passed, then reschedules
+    /// it for its next interval. Modules with slower data declare a longer
+    /// `INTERVAL` and are left alone in between, decoupling data cadence from
+    /// the render loop's own cadence.
+    pub fn exec_due(&mut self) -> Result<()> {
+        let now = Instant::now();
+        let due: Vec<String> = self
+            .modules
+            .keys()
+            .filter(|name| {
+                self.next_run
+                    .get(name.as_str())
+                    .map(|deadline| now >= *deadline)
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        for name in due {
+            let module = match self.modules.get(&name) {
+                Some(module) => module.clone(),
+                None => continue,
+            };
+            self.exec_one(&name, &module);
+            self.schedule_module(&name, &module);
+        }
         Ok(())
     }
+
+    fn exec_one(&mut self, name: &str, module: &Scope) {
+        let block: PyResult<()> = self.interpreter.enter(|vm| {
+            let module = module.clone();
+            vm.start_thread(move |vm| {
+                let res = module
+                    .locals
+                    .get_item("update", vm)
+                    .unwrap_or(vm.new_function("update", || {}).to_pyobject(vm));
+                let result = res.call((), vm);
+                match result {
+                    Err(e) => {
+                        let _ = log::println(&format!(
+                            "E: {}",
+                            e.clone().to_pyobject(vm).repr(vm).unwrap().as_str()
+                        ));
+                        let traceback = e.traceback().unwrap();
+                        for tb in traceback.iter() {
+                            let _ = log::println(&format!("Traceback: {:?}", tb.frame.code,));
+                        }
+                    }
+                    _ => {}
+                }
+            });
+            Ok(())
+        });
+        match block {
+            Ok(_) => {}
+            Err(e) => {
+                self.interpreter.enter(|vm| {
+                    let _ = log::println(&format!(
+                        "[{}] {}",
+                        name,
+                        e.as_object().repr(vm).unwrap().as_str()
+                    ));
+                });
+            }
+        }
+    }
+    fn spawn_terminal(&mut self, name: String, command: String, args: Vec<String>) -> Result<WidgetState<'static>> {
+        let pty_system = native_pty_system();
+        let pair = pty_system.openpty(PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+        let mut cmd = CommandBuilder::new(command);
+        cmd.args(args);
+        let child = pair.slave.spawn_command(cmd)?;
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader()?;
+        let parser = Arc::new(Mutex::new(vt100::Parser::new(24, 80, 0)));
+        let alive = Arc::new(AtomicBool::new(true));
+        let thread_parser = parser.clone();
+        let thread_alive = alive.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while thread_alive.load(Ordering::SeqCst) {
+                match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => thread_parser.lock().unwrap().process(&buf[..n]),
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(WidgetState::Terminal(TerminalWidget {
+            name,
+            parser,
+            master: Arc::new(Mutex::new(pair.master)),
+            child: Arc::new(Mutex::new(child)),
+            alive,
+            area: Rect::ZERO,
+        }))
+    }
     fn show_image(&mut self, name: String, path: String) -> Result<()> {
         let dyn_img = imageproc::image::io::Reader::open(path.to_owned())?.decode()?;
         let image = self.picker.new_resize_protocol(dyn_img);
@@ -862,6 +1442,7 @@ impl Widget for &mut App<'_> {
     where
         Self: Sized,
     {
+        self.widget_areas.lock().unwrap().clear();
         let size = if let Some((w, h)) = self.size {
             (w as u16, h as u16)
         } else {
@@ -1073,6 +1654,7 @@ impl Widget for &mut App<'_> {
                             name,
                             align,
                         }) => {
+                            self.widget_areas.lock().unwrap().insert(name.clone(), r);
                             Paragraph::new(text.as_str())
                                 .style(color.clone())
                                 .alignment(align)
@@ -1101,6 +1683,7 @@ impl Widget for &mut App<'_> {
                             y_labels,
                             y_title,
                         }) => {
+                            self.widget_areas.lock().unwrap().insert(name.clone(), r);
                             let color = Color::from_str(&color).unwrap_or(Color::White);
                             let x_color = Color::from_str(&x_color).unwrap_or(Color::White);
                             let y_color = Color::from_str(&y_color).unwrap_or(Color::White);
@@ -1143,6 +1726,7 @@ impl Widget for &mut App<'_> {
                             name,
                             border_color,
                         }) => {
+                            self.widget_areas.lock().unwrap().insert(name.clone(), r);
                             let mut text: Vec<Line<'_>> = vec![];
                             let mut line = vec![];
                             for s in span.iter() {
@@ -1170,6 +1754,7 @@ impl Widget for &mut App<'_> {
                                 .render(r, buf);
                         }
                         WidgetState::Image(ImageWidget { name, .. }) => {
+                            self.widget_areas.lock().unwrap().insert(name.clone(), r);
                             let img = match self.widgets.get_mut(name.as_str()) {
                                 Some(img) => img,
                                 None => continue,
@@ -1184,6 +1769,77 @@ impl Widget for &mut App<'_> {
                         WidgetState::BigText(BigTextWidget { big_text, area }) => {
                             big_text.render(area, buf)
                         }
+                        WidgetState::Code(CodeWidget { name, lines }) => {
+                            self.widget_areas.lock().unwrap().insert(name.clone(), r);
+                            Paragraph::new(lines)
+                                .wrap(Wrap { trim: false })
+                                .block(
+                                    block
+                                        .clone()
+                                        .title(name.as_str())
+                                        .padding(Padding::horizontal(1)),
+                                )
+                                .render(r, buf);
+                        }
+                        WidgetState::Terminal(TerminalWidget {
+                            name,
+                            parser,
+                            master,
+                            ..
+                        }) => {
+                            self.widget_areas.lock().unwrap().insert(name.clone(), r);
+                            let term_block = block.clone().title(name.as_str());
+                            let inner = term_block.inner(r);
+                            term_block.render(r, buf);
+                            let rows = inner.height.max(1);
+                            let cols = inner.width.max(1);
+                            if let Some(WidgetState::Terminal(term)) =
+                                self.widgets.get_mut(name.as_str())
+                            {
+                                if term.area != inner {
+                                    term.area = inner;
+                                    parser.lock().unwrap().set_size(rows, cols);
+                                    if let Ok(master) = master.lock() {
+                                        let _ = master.resize(PtySize {
+                                            rows,
+                                            cols,
+                                            pixel_width: 0,
+                                            pixel_height: 0,
+                                        });
+                                    }
+                                }
+                            }
+                            let parser = parser.lock().unwrap();
+                            let screen = parser.screen();
+                            for y in 0..rows {
+                                for x in 0..cols {
+                                    let cell = match screen.cell(y, x) {
+                                        Some(cell) => cell,
+                                        None => continue,
+                                    };
+                                    let mut style = Style::default()
+                                        .fg(vt100_to_ratatui_color(cell.fgcolor()))
+                                        .bg(vt100_to_ratatui_color(cell.bgcolor()));
+                                    if cell.bold() {
+                                        style = style.add_modifier(Modifier::BOLD);
+                                    }
+                                    if cell.italic() {
+                                        style = style.add_modifier(Modifier::ITALIC);
+                                    }
+                                    if cell.underline() {
+                                        style = style.add_modifier(Modifier::UNDERLINED);
+                                    }
+                                    if cell.inverse() {
+                                        style = style.add_modifier(Modifier::REVERSED);
+                                    }
+                                    let contents = cell.contents();
+                                    let symbol = if contents.is_empty() { " " } else { &contents };
+                                    buf.get_mut(inner.x + x, inner.y + y)
+                                        .set_symbol(symbol)
+                                        .set_style(style);
+                                }
+                            }
+                        }
                         WidgetState::Blank => {
                             Block::new().render(r, buf);
                         } // _ => {}
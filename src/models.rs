@@ -1,5 +1,11 @@
+// No training entry point calls into this module yet (no binary in this
+// crate runs an MFCC training loop); keep it compiled in rather than
+// rotting out of the tree, same as `tui::screenshot`.
+#![allow(dead_code)]
+
 use candle_core::{Device, IndexOp, Tensor};
 use candle_nn as nn;
+use candle_nn::Module as _;
 use ndarray::{Array, Array1};
 use rand::Rng;
 
@@ -95,17 +101,17 @@ impl SpecAugment {
     }
 }
 
-// impl nn::Module for SpecAugment {
-//     fn forward(&self, xs: &Tensor) -> candle_core::Result<Tensor> {
-//         let rng = rand::thread_rng();
-//         let probability: f64 = rng.gen_range(0.0..1.0);
-//         if probability > 0.5 {
-//             return 
-//         }
-//     }
-    
-// }
-
+impl nn::Module for SpecAugment {
+    fn forward(&self, xs: &Tensor) -> candle_core::Result<Tensor> {
+        let mut rng = rand::thread_rng();
+        let probability: f32 = rng.gen_range(0.0..1.0);
+        if probability > self.rate {
+            return Ok(xs.clone());
+        }
+        let xs = self.specaug.forward(xs)?;
+        self.specaug2.forward(&xs)
+    }
+}
 
 pub struct AxisMasking {
     mask_param: u32,
@@ -120,11 +126,62 @@ impl AxisMasking {
     }
 }
 
+/// Zeros a randomly sized, randomly positioned slice of `xs` along `axis`,
+/// sampling width `w` from `[0, min(mask_param, axis_len)]` and the start
+/// from `[0, axis_len - w]` so the slice never runs past the axis bound.
+/// `w == 0` and an empty axis are both no-ops.
+fn mask_axis(xs: &Tensor, axis: usize, mask_param: u32, rng: &mut impl Rng) -> candle_core::Result<Tensor> {
+    let axis_len = xs.dims()[axis];
+    if axis_len == 0 || mask_param == 0 {
+        return Ok(xs.clone());
+    }
+    let w = rng.gen_range(0..=mask_param.min(axis_len as u32)) as usize;
+    if w == 0 {
+        return Ok(xs.clone());
+    }
+    let s = rng.gen_range(0..=(axis_len - w));
+
+    let mut zero_dims = xs.dims().to_vec();
+    zero_dims[axis] = w;
+    let zeros = Tensor::zeros(zero_dims, xs.dtype(), xs.device())?;
+
+    let mut parts = Vec::with_capacity(3);
+    if s > 0 {
+        parts.push(xs.narrow(axis, 0, s)?);
+    }
+    parts.push(zeros);
+    let after = s + w;
+    if axis_len - after > 0 {
+        parts.push(xs.narrow(axis, after, axis_len - after)?);
+    }
+    Tensor::cat(&parts, axis)
+}
+
 impl nn::Module for AxisMasking {
     fn forward(&self, xs: &Tensor) -> candle_core::Result<Tensor> {
-        if self.iid_masks {
-            return 
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(0.0..1.0) > self.p {
+            return Ok(xs.clone());
         }
+
+        let dims = xs.dims();
+        let has_channels = dims.len() == 3;
+        let axis = self.axis as usize + if has_channels { 1 } else { 0 };
+        if axis >= dims.len() || dims[axis] == 0 {
+            return Ok(xs.clone());
+        }
+
+        if has_channels && self.iid_masks {
+            let channels = dims[0];
+            let mut masked = Vec::with_capacity(channels);
+            for c in 0..channels {
+                let channel = xs.narrow(0, c, 1)?;
+                masked.push(mask_axis(&channel, axis, self.mask_param, &mut rng)?);
+            }
+            return Tensor::cat(&masked, 0);
+        }
+
+        mask_axis(xs, axis, self.mask_param, &mut rng)
     }
 }
 
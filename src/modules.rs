@@ -5,13 +5,16 @@ use rustpython_vm::pymodule;
 
 #[pymodule]
 pub mod dashboard_sys {
+    use std::{collections::HashMap, time::Duration};
+
     use color_eyre::eyre::Result;
     use crossbeam_channel::Sender;
-    use futures::executor;
     use once_cell::sync::OnceCell;
-    use rustpython_vm::{PyObject, PyResult, TryFromBorrowedObject, VirtualMachine};
+    use rustpython_vm::{PyObject, PyObjectRef, PyResult, TryFromBorrowedObject, VirtualMachine};
+    use serde::Serialize;
     use serde_json::Value;
 
+    use crate::http::{self, HttpRequest, HttpResponse};
     use crate::log;
 
     #[pyfunction]
@@ -22,13 +25,17 @@ pub mod dashboard_sys {
     #[derive(Debug)]
     pub struct Instance {
         sender: Sender<FrameData>,
+        runtime: tokio::runtime::Handle,
     }
 
     pub static INSTANCE: OnceCell<Instance> = OnceCell::new();
 
     pub fn initialize(sender: Sender<FrameData>) {
         INSTANCE
-            .set(Instance { sender })
+            .set(Instance {
+                sender,
+                runtime: tokio::runtime::Handle::current(),
+            })
             .expect("initialize failed");
     }
 
@@ -57,13 +64,108 @@ pub mod dashboard_sys {
         let _ = INSTANCE.get().unwrap().sender.send(data);
     }
 
+    fn parse_headers(raw: &str) -> HashMap<String, String> {
+        if raw.is_empty() {
+            return HashMap::new();
+        }
+        serde_json::from_str(raw).unwrap_or_default()
+    }
+
+    fn http_response_to_pyobject(response: &HttpResponse, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let dict = vm.ctx.new_dict();
+        dict.set_item("status", vm.new_pyobj(response.status as i64), vm)?;
+        let headers = vm.ctx.new_dict();
+        for (key, value) in response.headers.iter() {
+            headers.set_item(key.as_str(), vm.new_pyobj(value.clone()), vm)?;
+        }
+        dict.set_item("headers", headers.into(), vm)?;
+        dict.set_item("body", vm.new_pyobj(response.body.clone()), vm)?;
+        Ok(dict.into())
+    }
+
+    /// Synchronous request/response call: sends `method url` (with optional
+    /// JSON-encoded `headers` and raw `body`, empty string for neither) and
+    /// waits, retrying transient failures (connection errors, 5xx) up to
+    /// `retries` times with exponential backoff.
     #[pyfunction]
-    pub fn fetch(method: String, url: String, vm: &VirtualMachine) -> PyResult<String> {
-        executor::block_on(async {
-            a_fetch(method, url)
-                .await
-                .map_err(|e| vm.new_runtime_error(e.to_string()))
-        })
+    pub fn fetch(
+        method: String,
+        url: String,
+        headers: String,
+        body: String,
+        timeout_secs: u64,
+        retries: u32,
+        vm: &VirtualMachine,
+    ) -> PyResult<PyObjectRef> {
+        let req = HttpRequest {
+            method,
+            url,
+            headers: parse_headers(&headers),
+            body: if body.is_empty() { None } else { Some(body) },
+            timeout: Duration::from_secs(if timeout_secs == 0 { 30 } else { timeout_secs }),
+        };
+        let handle = INSTANCE.get().unwrap().runtime.clone();
+        let response = handle
+            .block_on(http::request(req, retries))
+            .map_err(|e| vm.new_runtime_error(e.to_string()))?;
+        http_response_to_pyobject(&response, vm)
+    }
+
+    /// Async fire-and-forget dispatch: queues the request on the runtime
+    /// without blocking the caller, and pushes an `http_response` frame
+    /// through the `FrameData` channel under `name` once it lands.
+    #[pyfunction]
+    pub fn a_fetch(
+        method: String,
+        url: String,
+        headers: String,
+        body: String,
+        timeout_secs: u64,
+        name: String,
+    ) {
+        let req = HttpRequest {
+            method,
+            url,
+            headers: parse_headers(&headers),
+            body: if body.is_empty() { None } else { Some(body) },
+            timeout: Duration::from_secs(if timeout_secs == 0 { 30 } else { timeout_secs }),
+        };
+        let handle = INSTANCE.get().unwrap().runtime.clone();
+        handle.spawn(async move {
+            match http::request(req, 0).await {
+                Ok(response) => {
+                    let value = serde_json::to_value(&response).unwrap_or(Value::Null);
+                    send(FrameData {
+                        action: "http_response".to_string(),
+                        name,
+                        value,
+                    });
+                }
+                Err(e) => {
+                    let _ = log::println(&format!("HttpAsyncError: {:?}", e));
+                }
+            }
+        });
+    }
+
+    /// Requests that a `FrameData` frame be sent again after `seconds`,
+    /// once if `recurring` is false, or every `seconds` thereafter if true.
+    /// Lets a script drive one-shot or recurring updates outside of its
+    /// own per-module `INTERVAL` cadence.
+    #[pyfunction]
+    pub fn schedule(seconds: f64, action: String, name: String, value: String, recurring: bool) {
+        std::thread::spawn(move || loop {
+            std::thread::sleep(Duration::from_secs_f64(seconds.max(0.0)));
+            let value = serde_json::from_str(&value).unwrap_or(Value::Null);
+            send(FrameData {
+                action: action.clone(),
+                name: name.clone(),
+                value,
+            });
+            if !recurring {
+                break;
+            }
+        });
     }
 
     #[pyfunction]
@@ -71,13 +173,138 @@ pub mod dashboard_sys {
         send(FrameData { action: "reload".to_string(), name: "reload".to_owned(), value: Value::Null });
     }
 
-    async fn a_fetch(method: String, url: String) -> Result<String> {
-        let response;
-        if method == "GET" {
-            response = reqwest::get(url).await?.text().await?;
-        } else {
-            return Err(color_eyre::eyre::Error::msg("method incorrect"));
+    /// Starts recording rendered frames to `path` as an asciicast v2 stream.
+    /// Any recording already in progress is replaced.
+    #[pyfunction]
+    pub fn start_recording(path: String) {
+        send(FrameData {
+            action: "start_recording".to_string(),
+            name: "recording".to_owned(),
+            value: serde_json::json!({ "path": path }),
+        });
+    }
+
+    /// Stops the current recording, if any.
+    #[pyfunction]
+    pub fn stop_recording() {
+        send(FrameData {
+            action: "stop_recording".to_string(),
+            name: "recording".to_owned(),
+            value: Value::Null,
+        });
+    }
+
+    #[derive(Clone, PartialEq, Serialize)]
+    pub struct GitStatus {
+        pub branch: String,
+        pub ahead: usize,
+        pub behind: usize,
+        pub staged: usize,
+        pub modified: usize,
+        pub untracked: usize,
+    }
+
+    fn read_git_status(path: &str) -> Result<GitStatus> {
+        let repo = git2::Repository::open(path)?;
+        let head = repo.head().ok();
+        let branch = head
+            .as_ref()
+            .and_then(|h| h.shorthand())
+            .unwrap_or("HEAD")
+            .to_string();
+        let (ahead, behind) = head
+            .as_ref()
+            .filter(|h| h.is_branch())
+            .and_then(|h| h.target())
+            .and_then(|local| {
+                repo.find_branch(&branch, git2::BranchType::Local)
+                    .ok()
+                    .and_then(|b| b.upstream().ok())
+                    .and_then(|u| u.get().target())
+                    .map(|upstream| (local, upstream))
+            })
+            .and_then(|(local, upstream)| repo.graph_ahead_behind(local, upstream).ok())
+            .unwrap_or((0, 0));
+
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(true);
+        let statuses = repo.statuses(Some(&mut opts))?;
+        let mut staged = 0;
+        let mut modified = 0;
+        let mut untracked = 0;
+        for entry in statuses.iter() {
+            let status = entry.status();
+            if status.intersects(
+                git2::Status::INDEX_NEW
+                    | git2::Status::INDEX_MODIFIED
+                    | git2::Status::INDEX_DELETED
+                    | git2::Status::INDEX_RENAMED
+                    | git2::Status::INDEX_TYPECHANGE,
+            ) {
+                staged += 1;
+            }
+            if status.intersects(
+                git2::Status::WT_MODIFIED
+                    | git2::Status::WT_DELETED
+                    | git2::Status::WT_TYPECHANGE
+                    | git2::Status::WT_RENAMED,
+            ) {
+                modified += 1;
+            }
+            if status.contains(git2::Status::WT_NEW) {
+                untracked += 1;
+            }
         }
-        Ok(response)
+        Ok(GitStatus {
+            branch,
+            ahead,
+            behind,
+            staged,
+            modified,
+            untracked,
+        })
+    }
+
+    fn git_status_to_pyobject(status: &GitStatus, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let dict = vm.ctx.new_dict();
+        dict.set_item("branch", vm.new_pyobj(status.branch.clone()), vm)?;
+        dict.set_item("ahead", vm.new_pyobj(status.ahead as i64), vm)?;
+        dict.set_item("behind", vm.new_pyobj(status.behind as i64), vm)?;
+        dict.set_item("staged", vm.new_pyobj(status.staged as i64), vm)?;
+        dict.set_item("modified", vm.new_pyobj(status.modified as i64), vm)?;
+        dict.set_item("untracked", vm.new_pyobj(status.untracked as i64), vm)?;
+        Ok(dict.into())
+    }
+
+    #[pyfunction]
+    pub fn git_status(path: String, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+        let status = read_git_status(&path).map_err(|e| vm.new_runtime_error(e.to_string()))?;
+        git_status_to_pyobject(&status, vm)
+    }
+
+    /// Polls `path`'s repository every `interval_secs` and pushes a
+    /// `git_status` frame through the shared `FrameData` channel whenever the
+    /// branch, ahead/behind counts, or file counts change, so a repo-health
+    /// pane refreshes promptly instead of waiting on its module's next
+    /// `INTERVAL` run.
+    #[pyfunction]
+    pub fn watch_git_status(path: String, name: String, interval_secs: u64) {
+        std::thread::spawn(move || {
+            let mut last: Option<GitStatus> = None;
+            loop {
+                if let Ok(status) = read_git_status(&path) {
+                    if last.as_ref() != Some(&status) {
+                        let value = serde_json::to_value(&status).unwrap_or(Value::Null);
+                        send(FrameData {
+                            action: "git_status".to_string(),
+                            name: name.clone(),
+                            value,
+                        });
+                        last = Some(status);
+                    }
+                }
+                std::thread::sleep(Duration::from_secs(interval_secs.max(1)));
+            }
+        });
     }
 }
@@ -1,3 +1,7 @@
+// This crate has no JsRuntime anywhere that loads extensions, so nothing
+// here is reachable; left as the bare op_http_get from before rather than
+// growing more dead surface. The HTTP client work for dashboard_sys lives
+// in http.rs/modules.rs instead.
 use anyhow::Result;
 use deno_core::{extension, op2, Extension};
 
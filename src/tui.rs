@@ -1,5 +1,7 @@
 use std::{
+    fs::File,
     io::{self, stdout, Stdout, Write},
+    time::Instant,
     // sync::{Arc, Mutex},
 };
 
@@ -35,11 +37,60 @@ pub fn init() -> io::Result<TUI> {
     Terminal::new(CrosstermBackend::new(stdout()))
 }
 
+/// Returns a buffer the same size as `buffer` with every cell outside
+/// `allowed` blanked out, so a scoped token only streams the widgets it was
+/// granted visibility into.
+pub fn mask_buffer(buffer: &Buffer, allowed: &[Rect]) -> Buffer {
+    let mut masked = Buffer::empty(buffer.area);
+    for rect in allowed {
+        let rect = rect.intersection(buffer.area);
+        for y in rect.top()..rect.bottom() {
+            for x in rect.left()..rect.right() {
+                *masked.get_mut(x, y) = buffer.get(x, y).clone();
+            }
+        }
+    }
+    masked
+}
+
+/// Records rendered frames as an asciicast v2 stream: a JSON header line
+/// declaring terminal size, followed by one `[elapsed_seconds, "o", ansi]`
+/// event line per frame, flushed incrementally so a long session doesn't
+/// buffer the whole recording in memory.
+pub struct Recorder {
+    file: File,
+    started: Instant,
+}
+
+impl Recorder {
+    pub fn start(path: &str, width: u16, height: u16) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        let header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": 0,
+        });
+        writeln!(file, "{}", header)?;
+        Ok(Self {
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, ansi: &str) -> io::Result<()> {
+        let event = serde_json::json!([self.started.elapsed().as_secs_f64(), "o", ansi]);
+        writeln!(self.file, "{}", event)?;
+        self.file.flush()
+    }
+}
+
 pub fn to_ansi(current_buffer: Buffer, last_buffer: Buffer) -> String {
     let mut output = String::new();
     let updates = last_buffer.diff(&current_buffer);
     let mut fg = Color::Reset;
     let mut bg = Color::Reset;
+    let mut underline_color = Color::Reset;
     let mut modifier = Modifier::empty();
     let mut last_pos: Option<(u16, u16)> = None;
     for (x, y, cell) in updates.into_iter() {
@@ -107,9 +158,12 @@ pub fn to_ansi(current_buffer: Buffer, last_buffer: Buffer) -> String {
             fg = cell.fg;
             bg = cell.bg;
         }
+        if cell.underline_color != underline_color {
+            let _ = SetUnderlineColor(cell.underline_color.into()).write_ansi(&mut output);
+            underline_color = cell.underline_color;
+        }
         let _ = Print(cell.symbol()).write_ansi(&mut output);
     }
-    // TODO: Underline
     let _ = SetForegroundColor(CColor::Reset).write_ansi(&mut output);
     let _ = SetBackgroundColor(CColor::Reset).write_ansi(&mut output);
     let _ = SetUnderlineColor(CColor::Reset).write_ansi(&mut output);
@@ -118,6 +172,49 @@ pub fn to_ansi(current_buffer: Buffer, last_buffer: Buffer) -> String {
     output
 }
 
+/// The 16 standard ANSI colors, indices 0-15, with the same VGA-style values
+/// `to_rgb` uses for the named colors.
+const XTERM_16: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [170, 0, 0],
+    [0, 170, 0],
+    [170, 85, 0],
+    [0, 0, 170],
+    [170, 0, 170],
+    [0, 170, 170],
+    [170, 170, 170],
+    [85, 85, 85],
+    [255, 85, 85],
+    [85, 255, 85],
+    [255, 255, 85],
+    [85, 85, 255],
+    [255, 85, 255],
+    [85, 255, 255],
+    [255, 255, 255],
+];
+
+/// The 6 steps used per channel of the xterm 6x6x6 color cube (indices 16-231).
+const XTERM_CUBE_STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Resolves an xterm-256 palette index to an RGB triple: the 16 standard
+/// ANSI colors, the 6x6x6 color cube, then the 24-step grayscale ramp.
+fn xterm_256_to_rgb(n: u8) -> [u8; 3] {
+    match n {
+        0..=15 => XTERM_16[n as usize],
+        16..=231 => {
+            let i = n - 16;
+            let r = XTERM_CUBE_STEPS[(i / 36) as usize];
+            let g = XTERM_CUBE_STEPS[((i / 6) % 6) as usize];
+            let b = XTERM_CUBE_STEPS[(i % 6) as usize];
+            [r, g, b]
+        }
+        232..=255 => {
+            let level = 8 + (n - 232) * 10;
+            [level, level, level]
+        }
+    }
+}
+
 #[allow(dead_code)]
 fn to_rgb(color: Color) -> Rgb<u8> {
     let ansi: [u8; 3] = match color {
@@ -126,7 +223,7 @@ fn to_rgb(color: Color) -> Rgb<u8> {
         Color::Cyan => [0, 170, 170],
         Color::DarkGray => [85, 85, 85],
         Color::Green => [0, 170, 0],
-        Color::Indexed(_) => [255, 255, 255],
+        Color::Indexed(n) => xterm_256_to_rgb(n),
         Color::LightBlue => [85, 85, 255],
         Color::LightCyan => [85, 255, 255],
         Color::LightGreen => [85, 255, 85],